@@ -1,48 +1,234 @@
-use std::collections::HashMap;
+use std::collections::HashSet;
 use std::error::Error as ErrorTrait;
 use std::fmt;
 use std::fs;
+use std::iter::FromIterator;
 use std::path::Path;
 
-use serde::{Deserialize, Serialize};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Serialize, Serializer};
 
-use crate::parser::bibfile;
+use crate::parser::parse_diagnostic;
 
 pub type BibFile = Vec<BibItem>;
 
-#[derive(Debug)]
-pub struct BibError;
+/// A failure from [`BibItem::load`]: either the file couldn't be read, or it
+/// was read but didn't parse.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BibError {
+    /// The file couldn't be read at all (e.g. it doesn't exist).
+    Io(String),
+    /// The file was read but parsing failed at the first unparsable entry.
+    ///
+    /// `partial` holds every entry successfully parsed before the failure,
+    /// so callers that only want best-effort results aren't forced to
+    /// discard them.
+    Parse {
+        offset: usize,
+        line: usize,
+        column: usize,
+        label: Option<String>,
+        message: String,
+        partial: BibFile,
+    },
+}
 
 impl fmt::Display for BibError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "An error occurred whilst parsing the file.")
+        match self {
+            BibError::Io(message) => write!(f, "{}", message),
+            BibError::Parse {
+                offset,
+                line,
+                column,
+                label,
+                message,
+                ..
+            } => {
+                write!(
+                    f,
+                    "parse error at line {}, column {} (byte offset {})",
+                    line, column, offset
+                )?;
+                if let Some(label) = label {
+                    write!(f, ", near entry '{}'", label)?;
+                }
+                write!(f, ": {}", message)
+            }
+        }
     }
 }
 
-impl ErrorTrait for BibError {
-    fn description(&self) -> &str {
-        "An error occurred whilst parsing the file."
+impl ErrorTrait for BibError {}
+
+impl From<std::io::Error> for BibError {
+    fn from(err: std::io::Error) -> BibError {
+        BibError::Io(err.to_string())
     }
 }
 
-impl From<std::io::Error> for BibError {
-    fn from(_err: std::io::Error) -> BibError {
-        BibError
+/// A single piece of a tag value, as produced by the `value` grammar.
+///
+/// BibTeX allows a field value to be built from several `#`-concatenated
+/// pieces. A piece is either literal text (a quoted/braced string or a bare
+/// number) or a bare identifier referring to an `@STRING` macro, which is
+/// only resolved once the whole file has been parsed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ValueFragment {
+    Literal(String),
+    Ident(String),
+}
+
+pub type Value = Vec<ValueFragment>;
+
+/// An insertion-order-preserving map from tag name to value.
+///
+/// BibTeX field order is meaningful to authors and tools alike, and a plain
+/// `HashMap` made both `Display` and serialization output nondeterministic
+/// from run to run. `TagMap` keeps tags in the order they were first seen.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TagMap(Vec<(String, Value)>);
+
+impl Serialize for TagMap {
+    /// Serialize as a real JSON object (`{"title": "A Title", ...}`) rather
+    /// than the derived array-of-pairs, and flatten each value down to its
+    /// concatenated string via [`format_value`] rather than exposing the
+    /// `ValueFragment` provenance. This keeps tag order and is what
+    /// consumers of [`BibItem::to_json`](crate::BibItem::to_json) expect.
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (key, value) in &self.0 {
+            map.serialize_entry(key, &format_value(value))?;
+        }
+        map.end()
     }
 }
 
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+impl TagMap {
+    pub fn new() -> Self {
+        TagMap(Vec::new())
+    }
+
+    /// Insert `value` under `key`, returning the previous value if `key`
+    /// was already present. An existing key keeps its original position.
+    pub fn insert(&mut self, key: String, value: Value) -> Option<Value> {
+        match self.0.iter_mut().find(|(k, _)| *k == key) {
+            Some(entry) => Some(std::mem::replace(&mut entry.1, value)),
+            None => {
+                self.0.push((key, value));
+                None
+            }
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.0.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Value)> {
+        self.0.iter().map(|(k, v)| (k, v))
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut Value> {
+        self.0.iter_mut().map(|(_, v)| v)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl FromIterator<(String, Value)> for TagMap {
+    fn from_iter<I: IntoIterator<Item = (String, Value)>>(iter: I) -> Self {
+        let mut map = TagMap::new();
+        for (k, v) in iter {
+            map.insert(k, v);
+        }
+        map
+    }
+}
+
+/// `BibItem` is serialize-only: its hand-written [`Serialize`] impl flattens
+/// `tags` into a plain JSON object and tags non-`Entry` variants with a
+/// `"type"` field, a shape the derived `Deserialize` for an untagged enum
+/// cannot parse back. There's no `Deserialize` impl for either `BibItem` or
+/// [`TagMap`]; if one is ever needed it must be hand-written to match.
+#[derive(Debug, Clone, PartialEq)]
 pub enum BibItem {
-    String(HashMap<String, String>),
-    Preamble,
-    Comment,
+    String(TagMap),
+    Preamble(String),
+    Comment(String),
     Entry {
         entry_type: String,
         label: String,
-        tags: HashMap<String, String>,
+        tags: TagMap,
     },
 }
 
+impl Serialize for BibItem {
+    /// `Entry` keeps its plain `{entry_type, label, tags}` shape (no
+    /// variant-name wrapper), since `entry_type` already identifies it.
+    /// `Preamble` and `Comment` both wrap a bare `String`, so without a
+    /// `"type"` field they'd serialize as indistinguishable JSON strings
+    /// (and be indistinguishable from a stray `String` value, too); give
+    /// every non-`Entry` variant a `"type"` discriminant so `to_json`
+    /// output unambiguously tags each variant's shape.
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        use serde::ser::SerializeStruct;
+        match self {
+            BibItem::Entry {
+                entry_type,
+                label,
+                tags,
+            } => {
+                let mut s = serializer.serialize_struct("BibItem", 3)?;
+                s.serialize_field("entry_type", entry_type)?;
+                s.serialize_field("label", label)?;
+                s.serialize_field("tags", tags)?;
+                s.end()
+            }
+            BibItem::String(tags) => {
+                let mut s = serializer.serialize_struct("BibItem", 2)?;
+                s.serialize_field("type", "string")?;
+                s.serialize_field("tags", tags)?;
+                s.end()
+            }
+            BibItem::Preamble(content) => {
+                let mut s = serializer.serialize_struct("BibItem", 2)?;
+                s.serialize_field("type", "preamble")?;
+                s.serialize_field("content", content)?;
+                s.end()
+            }
+            BibItem::Comment(content) => {
+                let mut s = serializer.serialize_struct("BibItem", 2)?;
+                s.serialize_field("type", "comment")?;
+                s.serialize_field("content", content)?;
+                s.end()
+            }
+        }
+    }
+}
+
+pub(crate) fn format_value(value: &[ValueFragment]) -> String {
+    value
+        .iter()
+        .map(|frag| match frag {
+            ValueFragment::Literal(s) => s.clone(),
+            ValueFragment::Ident(s) => s.clone(),
+        })
+        .collect()
+}
+
 impl fmt::Display for BibItem {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         use BibItem::*;
@@ -54,30 +240,349 @@ impl fmt::Display for BibItem {
             } => {
                 writeln!(f, "@{}{{{},", entry_type, label)?;
                 for (k, v) in tags.iter() {
-                    writeln!(f, "    {} = {{{}}},", k, v)?;
+                    writeln!(f, "    {} = {{{}}},", k, format_value(v))?;
                 }
                 write!(f, "}}\n\n")?;
             }
-            Preamble => {
-                write!(f, "Preamble")?;
+            Preamble(content) => {
+                write!(f, "@PREAMBLE{{{}}}\n\n", content)?;
             }
-            Comment => {
-                write!(f, "Comment")?;
+            Comment(content) => {
+                write!(f, "@COMMENT{{{}}}\n\n", content)?;
             }
-            String(_) => {
-                write!(f, "String")?;
+            String(tags) => {
+                write!(f, "@STRING{{")?;
+                for (i, (k, v)) in tags.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{} = {{{}}}", k, format_value(v))?;
+                }
+                write!(f, "}}\n\n")?;
             }
         }
         Ok(())
     }
 }
 
+/// Resolve a single `@STRING` macro by name, expanding any macros it refers
+/// to in turn. Already-resolved macros are served from `resolved`; a name
+/// that isn't a known macro, or that forms a cycle, is left verbatim.
+fn resolve_macro(
+    name: &str,
+    macros: &TagMap,
+    resolved: &mut std::collections::HashMap<String, String>,
+    visiting: &mut HashSet<String>,
+) -> String {
+    // Macro names are stored lower-cased by `tag_list`, so references must be
+    // looked up the same way regardless of how they're cased at the use site.
+    let key = name.to_lowercase();
+    if let Some(value) = resolved.get(&key) {
+        return value.clone();
+    }
+    let fragments = match macros.get(&key) {
+        Some(fragments) => fragments.clone(),
+        None => return name.to_string(),
+    };
+    if !visiting.insert(key.clone()) {
+        // Cyclic @STRING definition: leave the reference verbatim rather
+        // than recursing forever.
+        return name.to_string();
+    }
+    let expanded = expand_value(&fragments, macros, resolved, visiting);
+    visiting.remove(&key);
+    resolved.insert(key, expanded.clone());
+    expanded
+}
+
+/// Concatenate a tag's fragments into a single string, substituting any
+/// `@STRING` macro references found along the way.
+fn expand_value(
+    value: &[ValueFragment],
+    macros: &TagMap,
+    resolved: &mut std::collections::HashMap<String, String>,
+    visiting: &mut HashSet<String>,
+) -> String {
+    let mut out = String::new();
+    for fragment in value {
+        match fragment {
+            ValueFragment::Literal(s) => out.push_str(s),
+            ValueFragment::Ident(name) => {
+                out.push_str(&resolve_macro(name, macros, resolved, visiting))
+            }
+        }
+    }
+    out
+}
+
 impl BibItem {
     pub fn load(path: &Path) -> Result<BibFile, BibError> {
         let file_string = fs::read_to_string(path)?;
-        match bibfile(&file_string) {
-            Ok((_, file)) => Ok(file),
-            Err(_) => Err(BibError),
+        parse_diagnostic(&file_string)
+    }
+
+    /// The raw (flattened) value of `key` on this entry, if it is an
+    /// `Entry` and the tag is present.
+    pub fn tag(&self, key: &str) -> Option<String> {
+        match self {
+            BibItem::Entry { tags, .. } => tags.get(&key.to_lowercase()).map(|v| format_value(v)),
+            _ => None,
+        }
+    }
+
+    /// Resolve all `@STRING` macro definitions and `#`-concatenation across
+    /// `file`, in place, so that every `Entry` tag value becomes a single
+    /// fully expanded literal fragment. Unknown identifiers are left
+    /// verbatim rather than treated as an error.
+    pub fn resolve_strings(file: &mut BibFile) {
+        let mut macros = TagMap::new();
+        for item in file.iter() {
+            if let BibItem::String(defs) = item {
+                for (k, v) in defs.iter() {
+                    macros.insert(k.clone(), v.clone());
+                }
+            }
+        }
+
+        let mut resolved = std::collections::HashMap::new();
+        for item in file.iter_mut() {
+            if let BibItem::Entry { tags, .. } = item {
+                for value in tags.values_mut() {
+                    let mut visiting = HashSet::new();
+                    let expanded = expand_value(value, &macros, &mut resolved, &mut visiting);
+                    *value = vec![ValueFragment::Literal(expanded)];
+                }
+            }
+        }
+    }
+
+    /// Serialize `file` to JSON, preserving each entry's original tag order.
+    pub fn to_json(file: &BibFile) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(file)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag_map(pairs: Vec<(&str, Value)>) -> TagMap {
+        pairs
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect()
+    }
+
+    #[test]
+    fn test_load_missing_file_is_an_io_error_not_a_fake_parse_error() {
+        let path = Path::new("/nonexistent/path/does-not-exist.bib");
+        let err = BibItem::load(path).unwrap_err();
+        assert!(matches!(err, BibError::Io(_)));
+        assert!(!err.to_string().starts_with("parse error"));
+    }
+
+    #[test]
+    fn test_resolve_strings_substitutes_macro() {
+        let mut file: BibFile = vec![
+            BibItem::String(tag_map(vec![(
+                "pub",
+                vec![ValueFragment::Literal(String::from("Some Press"))],
+            )])),
+            BibItem::Entry {
+                entry_type: String::from("BOOK"),
+                label: String::from("label"),
+                tags: tag_map(vec![(
+                    "publisher",
+                    vec![ValueFragment::Ident(String::from("pub"))],
+                )]),
+            },
+        ];
+
+        BibItem::resolve_strings(&mut file);
+
+        match &file[1] {
+            BibItem::Entry { tags, .. } => {
+                assert_eq!(format_value(tags.get("publisher").unwrap()), "Some Press");
+            }
+            _ => panic!("expected an entry"),
+        }
+    }
+
+    #[test]
+    fn test_resolve_strings_concatenates_fragments() {
+        let mut file: BibFile = vec![
+            BibItem::String(tag_map(vec![
+                ("pre", vec![ValueFragment::Literal(String::from("Jane"))]),
+                ("post", vec![ValueFragment::Literal(String::from("Doe"))]),
+            ])),
+            BibItem::Entry {
+                entry_type: String::from("ARTICLE"),
+                label: String::from("label"),
+                tags: tag_map(vec![(
+                    "author",
+                    vec![
+                        ValueFragment::Ident(String::from("pre")),
+                        ValueFragment::Literal(String::from(" and ")),
+                        ValueFragment::Ident(String::from("post")),
+                    ],
+                )]),
+            },
+        ];
+
+        BibItem::resolve_strings(&mut file);
+
+        match &file[1] {
+            BibItem::Entry { tags, .. } => {
+                assert_eq!(format_value(tags.get("author").unwrap()), "Jane and Doe");
+            }
+            _ => panic!("expected an entry"),
+        }
+    }
+
+    #[test]
+    fn test_resolve_strings_leaves_unknown_identifier_verbatim() {
+        let mut file: BibFile = vec![BibItem::Entry {
+            entry_type: String::from("ARTICLE"),
+            label: String::from("label"),
+            tags: tag_map(vec![(
+                "publisher",
+                vec![ValueFragment::Ident(String::from("unknown"))],
+            )]),
+        }];
+
+        BibItem::resolve_strings(&mut file);
+
+        match &file[0] {
+            BibItem::Entry { tags, .. } => {
+                assert_eq!(format_value(tags.get("publisher").unwrap()), "unknown");
+            }
+            _ => panic!("expected an entry"),
+        }
+    }
+
+    #[test]
+    fn test_resolve_strings_matches_macro_name_case_insensitively() {
+        let mut file: BibFile = vec![
+            BibItem::String(tag_map(vec![(
+                "jsmith",
+                vec![ValueFragment::Literal(String::from("John Smith"))],
+            )])),
+            BibItem::Entry {
+                entry_type: String::from("ARTICLE"),
+                label: String::from("label"),
+                tags: tag_map(vec![(
+                    "author",
+                    vec![ValueFragment::Ident(String::from("JSmith"))],
+                )]),
+            },
+        ];
+
+        BibItem::resolve_strings(&mut file);
+
+        match &file[1] {
+            BibItem::Entry { tags, .. } => {
+                assert_eq!(format_value(tags.get("author").unwrap()), "John Smith");
+            }
+            _ => panic!("expected an entry"),
         }
     }
+
+    #[test]
+    fn test_resolve_strings_detects_cycle() {
+        let mut file: BibFile = vec![
+            BibItem::String(tag_map(vec![
+                ("a", vec![ValueFragment::Ident(String::from("b"))]),
+                ("b", vec![ValueFragment::Ident(String::from("a"))]),
+            ])),
+            BibItem::Entry {
+                entry_type: String::from("ARTICLE"),
+                label: String::from("label"),
+                tags: tag_map(vec![(
+                    "title",
+                    vec![ValueFragment::Ident(String::from("a"))],
+                )]),
+            },
+        ];
+
+        // Should terminate rather than recursing forever.
+        BibItem::resolve_strings(&mut file);
+
+        match &file[1] {
+            BibItem::Entry { tags, .. } => {
+                assert_eq!(format_value(tags.get("title").unwrap()), "a");
+            }
+            _ => panic!("expected an entry"),
+        }
+    }
+
+    #[test]
+    fn test_tag_map_preserves_insertion_order() {
+        let map = tag_map(vec![
+            ("title", vec![ValueFragment::Literal(String::from("A"))]),
+            ("author", vec![ValueFragment::Literal(String::from("B"))]),
+            ("year", vec![ValueFragment::Literal(String::from("C"))]),
+        ]);
+        let keys: Vec<&str> = map.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, vec!["title", "author", "year"]);
+    }
+
+    #[test]
+    fn test_display_emits_tags_in_order() {
+        let entry = BibItem::Entry {
+            entry_type: String::from("ARTICLE"),
+            label: String::from("label"),
+            tags: tag_map(vec![
+                ("title", vec![ValueFragment::Literal(String::from("A"))]),
+                ("author", vec![ValueFragment::Literal(String::from("B"))]),
+            ]),
+        };
+        let rendered = format!("{}", entry);
+        let title_pos = rendered.find("title").unwrap();
+        let author_pos = rendered.find("author").unwrap();
+        assert!(title_pos < author_pos);
+    }
+
+    #[test]
+    fn test_display_round_trips_string_macro_as_valid_bibtex() {
+        let item = BibItem::String(tag_map(vec![(
+            "pub",
+            vec![ValueFragment::Literal(String::from("Some Press"))],
+        )]));
+        assert_eq!(format!("{}", item), "@STRING{pub = {Some Press}}\n\n");
+    }
+
+    #[test]
+    fn test_to_json_emits_tags_as_an_object_of_flattened_strings() {
+        let file: BibFile = vec![BibItem::Entry {
+            entry_type: String::from("ARTICLE"),
+            label: String::from("label"),
+            tags: tag_map(vec![
+                ("title", vec![ValueFragment::Literal(String::from("A Title"))]),
+                ("year", vec![ValueFragment::Literal(String::from("2000"))]),
+            ]),
+        }];
+
+        let json = BibItem::to_json(&file).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            value[0]["tags"],
+            serde_json::json!({"title": "A Title", "year": "2000"})
+        );
+    }
+
+    #[test]
+    fn test_to_json_distinguishes_preamble_from_comment() {
+        let file: BibFile = vec![
+            BibItem::Preamble(String::from("\\makeatletter")),
+            BibItem::Comment(String::from("a note")),
+        ];
+
+        let json = BibItem::to_json(&file).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value[0]["type"], "preamble");
+        assert_eq!(value[0]["content"], "\\makeatletter");
+        assert_eq!(value[1]["type"], "comment");
+        assert_eq!(value[1]["content"], "a note");
+        assert_ne!(value[0], value[1]);
+    }
 }
@@ -0,0 +1,161 @@
+//! CSL-JSON export: maps `BibItem::Entry` values onto the subset of the
+//! Citation Style Language JSON schema that citation processors expect, so
+//! a loaded `BibFile` can feed tools like citeproc-js or Pandoc directly.
+
+use serde::Serialize;
+
+use crate::bibfile::BibFile;
+use crate::dates::{Date, PartialDate};
+use crate::BibItem;
+
+#[derive(Debug, Serialize)]
+pub struct CslName {
+    pub family: String,
+    pub given: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CslDate {
+    #[serde(rename = "date-parts")]
+    pub date_parts: Vec<Vec<i32>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CslItem {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub item_type: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub author: Vec<CslName>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issued: Option<CslDate>,
+    #[serde(rename = "container-title")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container_title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub publisher: Option<String>,
+}
+
+/// Map a BibTeX entry type onto the closest CSL item type.
+fn csl_type(entry_type: &str) -> &'static str {
+    match entry_type.to_uppercase().as_str() {
+        "ARTICLE" => "article-journal",
+        "BOOK" | "PROCEEDINGS" | "MANUAL" => "book",
+        "INBOOK" | "INCOLLECTION" => "chapter",
+        "INPROCEEDINGS" | "CONFERENCE" => "paper-conference",
+        "PHDTHESIS" | "MASTERSTHESIS" => "thesis",
+        "TECHREPORT" => "report",
+        "UNPUBLISHED" => "manuscript",
+        "BOOKLET" => "pamphlet",
+        _ => "document",
+    }
+}
+
+fn csl_name(name: &crate::names::Name) -> CslName {
+    let family = if name.von.is_empty() {
+        name.last.clone()
+    } else {
+        format!("{} {}", name.von, name.last)
+    };
+    CslName {
+        family,
+        given: name.first.clone(),
+    }
+}
+
+fn partial_date_parts(date: &PartialDate) -> Vec<i32> {
+    let mut parts = vec![date.year];
+    if let Some(month) = date.month {
+        parts.push(i32::from(month));
+        if let Some(day) = date.day {
+            parts.push(i32::from(day));
+        }
+    }
+    parts
+}
+
+fn csl_date(date: &Date) -> CslDate {
+    match date {
+        Date::Single(d) => CslDate {
+            date_parts: vec![partial_date_parts(d)],
+        },
+        Date::Range(start, end) => {
+            let mut date_parts = vec![partial_date_parts(start)];
+            if let Some(end) = end {
+                date_parts.push(partial_date_parts(end));
+            }
+            CslDate { date_parts }
+        }
+    }
+}
+
+/// Build a `CslItem` from an `Entry`, or `None` for any other `BibItem`
+/// variant (`@STRING`/`@PREAMBLE`/`@COMMENT` have no CSL representation).
+fn to_csl_item(item: &BibItem) -> Option<CslItem> {
+    match item {
+        BibItem::Entry {
+            entry_type, label, ..
+        } => Some(CslItem {
+            id: label.clone(),
+            item_type: csl_type(entry_type).to_string(),
+            author: item.authors().iter().map(csl_name).collect(),
+            title: item.display_value("title"),
+            issued: item.date().ok().flatten().as_ref().map(csl_date),
+            container_title: item
+                .display_value("journal")
+                .or_else(|| item.display_value("booktitle")),
+            publisher: item.display_value("publisher"),
+        }),
+        _ => None,
+    }
+}
+
+impl BibItem {
+    /// Export every `Entry` in `file` as CSL-JSON.
+    pub fn to_csl_json(file: &BibFile) -> Result<String, serde_json::Error> {
+        let items: Vec<CslItem> = file.iter().filter_map(to_csl_item).collect();
+        serde_json::to_string_pretty(&items)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bibfile::{TagMap, ValueFragment};
+
+    #[test]
+    fn test_article_maps_to_article_journal() {
+        let mut tags = TagMap::new();
+        tags.insert(
+            String::from("title"),
+            vec![ValueFragment::Literal(String::from("A Title"))],
+        );
+        tags.insert(
+            String::from("author"),
+            vec![ValueFragment::Literal(String::from("John Smith"))],
+        );
+        tags.insert(
+            String::from("date"),
+            vec![ValueFragment::Literal(String::from("2000"))],
+        );
+        let file: BibFile = vec![BibItem::Entry {
+            entry_type: String::from("ARTICLE"),
+            label: String::from("label"),
+            tags,
+        }];
+
+        let json = BibItem::to_csl_json(&file).unwrap();
+        assert!(json.contains("\"article-journal\""));
+        assert!(json.contains("\"family\": \"Smith\""));
+        assert!(json.contains("\"date-parts\""));
+    }
+
+    #[test]
+    fn test_non_entry_items_are_skipped() {
+        let file: BibFile = vec![BibItem::Comment(String::from("skip me"))];
+        let json = BibItem::to_csl_json(&file).unwrap();
+        assert_eq!(json, "[]");
+    }
+}
@@ -0,0 +1,266 @@
+//! Structured parsing of BibTeX/biblatex date fields (`date`, `year`,
+//! `month`, `urldate`) into [`Date`], following biblatex's date handling:
+//! ISO-style `YYYY`, `YYYY-MM`, `YYYY-MM-DD`, open/closed ranges
+//! (`YYYY/YYYY`), and the legacy `year`/`month` pair with three-letter
+//! month abbreviations.
+
+use std::error::Error as ErrorTrait;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::BibItem;
+
+/// A year, with an optional month and day: a single date, or one end of a
+/// date range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PartialDate {
+    pub year: i32,
+    pub month: Option<u8>,
+    pub day: Option<u8>,
+}
+
+/// A parsed date field: either a single point in time, or a `/`-separated
+/// range. A range with no end (`YYYY/`) is open-ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Date {
+    Single(PartialDate),
+    Range(PartialDate, Option<PartialDate>),
+}
+
+/// A date field could not be parsed; it is malformed rather than simply
+/// absent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DateError {
+    InvalidYear(String),
+    InvalidMonth(String),
+    InvalidDay(String),
+}
+
+impl fmt::Display for DateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DateError::InvalidYear(s) => write!(f, "invalid year: {:?}", s),
+            DateError::InvalidMonth(s) => write!(f, "invalid month: {:?}", s),
+            DateError::InvalidDay(s) => write!(f, "invalid day: {:?}", s),
+        }
+    }
+}
+
+impl ErrorTrait for DateError {}
+
+const MONTH_NAMES: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+
+fn parse_year(s: &str) -> Result<i32, DateError> {
+    s.trim()
+        .parse()
+        .map_err(|_| DateError::InvalidYear(s.to_string()))
+}
+
+fn parse_month(s: &str) -> Result<u8, DateError> {
+    let trimmed = s.trim();
+    if let Some(pos) = MONTH_NAMES
+        .iter()
+        .position(|name| trimmed.to_lowercase().starts_with(name))
+    {
+        return Ok(pos as u8 + 1);
+    }
+    let month: u8 = trimmed
+        .parse()
+        .map_err(|_| DateError::InvalidMonth(s.to_string()))?;
+    if (1..=12).contains(&month) {
+        Ok(month)
+    } else {
+        Err(DateError::InvalidMonth(s.to_string()))
+    }
+}
+
+fn parse_day(s: &str) -> Result<u8, DateError> {
+    let day: u8 = s
+        .trim()
+        .parse()
+        .map_err(|_| DateError::InvalidDay(s.to_string()))?;
+    if (1..=31).contains(&day) {
+        Ok(day)
+    } else {
+        Err(DateError::InvalidDay(s.to_string()))
+    }
+}
+
+/// Parse a single `YYYY`, `YYYY-MM` or `YYYY-MM-DD` component.
+fn parse_partial_date(s: &str) -> Result<PartialDate, DateError> {
+    let mut parts = s.trim().splitn(3, '-');
+    let year = parse_year(parts.next().unwrap_or(""))?;
+    let month = match parts.next() {
+        Some(m) => Some(parse_month(m)?),
+        None => None,
+    };
+    let day = match parts.next() {
+        Some(d) => Some(parse_day(d)?),
+        None => None,
+    };
+    Ok(PartialDate { year, month, day })
+}
+
+/// Parse a full `date`/`urldate`-style field, including `/`-separated
+/// ranges.
+fn parse_date_field(s: &str) -> Result<Date, DateError> {
+    let mut parts = s.trim().splitn(2, '/');
+    let start = parse_partial_date(parts.next().unwrap_or(""))?;
+    match parts.next() {
+        None => Ok(Date::Single(start)),
+        Some(end) if end.trim().is_empty() => Ok(Date::Range(start, None)),
+        Some(end) => Ok(Date::Range(start, Some(parse_partial_date(end)?))),
+    }
+}
+
+impl BibItem {
+    /// Parse `field` (e.g. `"date"`, `"urldate"`) as a date, if present.
+    pub fn date_field(&self, field: &str) -> Result<Option<Date>, DateError> {
+        match self.tag(field) {
+            Some(raw) => parse_date_field(&raw).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// The entry's publication date, from a unified `date` field if present,
+    /// otherwise combined from the legacy separate `year`/`month` fields.
+    pub fn date(&self) -> Result<Option<Date>, DateError> {
+        if let Some(raw) = self.tag("date") {
+            return parse_date_field(&raw).map(Some);
+        }
+
+        match self.tag("year") {
+            Some(year_raw) => {
+                let year = parse_year(&year_raw)?;
+                let month = match self.tag("month") {
+                    Some(month_raw) => Some(parse_month(&month_raw)?),
+                    None => None,
+                };
+                Ok(Some(Date::Single(PartialDate {
+                    year,
+                    month,
+                    day: None,
+                })))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// The entry's `urldate` field, parsed the same way as `date`.
+    pub fn urldate(&self) -> Result<Option<Date>, DateError> {
+        self.date_field("urldate")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::bibfile::{TagMap, ValueFragment};
+    use crate::BibItem;
+
+    fn entry_with(tags: &[(&str, &str)]) -> BibItem {
+        let mut hm = TagMap::new();
+        for (k, v) in tags {
+            hm.insert(
+                String::from(*k),
+                vec![ValueFragment::Literal(String::from(*v))],
+            );
+        }
+        BibItem::Entry {
+            entry_type: String::from("ARTICLE"),
+            label: String::from("label"),
+            tags: hm,
+        }
+    }
+
+    #[test]
+    fn test_year_only() {
+        let entry = entry_with(&[("date", "2000")]);
+        assert_eq!(
+            entry.date().unwrap(),
+            Some(Date::Single(PartialDate {
+                year: 2000,
+                month: None,
+                day: None
+            }))
+        );
+    }
+
+    #[test]
+    fn test_year_month_day() {
+        let entry = entry_with(&[("date", "2000-01-02")]);
+        assert_eq!(
+            entry.date().unwrap(),
+            Some(Date::Single(PartialDate {
+                year: 2000,
+                month: Some(1),
+                day: Some(2)
+            }))
+        );
+    }
+
+    #[test]
+    fn test_closed_range() {
+        let entry = entry_with(&[("date", "2000/2001")]);
+        assert_eq!(
+            entry.date().unwrap(),
+            Some(Date::Range(
+                PartialDate {
+                    year: 2000,
+                    month: None,
+                    day: None
+                },
+                Some(PartialDate {
+                    year: 2001,
+                    month: None,
+                    day: None
+                })
+            ))
+        );
+    }
+
+    #[test]
+    fn test_open_range() {
+        let entry = entry_with(&[("date", "2000/")]);
+        assert_eq!(
+            entry.date().unwrap(),
+            Some(Date::Range(
+                PartialDate {
+                    year: 2000,
+                    month: None,
+                    day: None
+                },
+                None
+            ))
+        );
+    }
+
+    #[test]
+    fn test_legacy_year_and_month_abbreviation() {
+        let entry = entry_with(&[("year", "2000"), ("month", "jan")]);
+        assert_eq!(
+            entry.date().unwrap(),
+            Some(Date::Single(PartialDate {
+                year: 2000,
+                month: Some(1),
+                day: None
+            }))
+        );
+    }
+
+    #[test]
+    fn test_missing_date_fields() {
+        let entry = entry_with(&[]);
+        assert_eq!(entry.date().unwrap(), None);
+    }
+
+    #[test]
+    fn test_invalid_month_is_recoverable_error() {
+        let entry = entry_with(&[("date", "2000-13")]);
+        assert!(entry.date().is_err());
+    }
+}
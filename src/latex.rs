@@ -0,0 +1,295 @@
+//! Opt-in LaTeX-to-Unicode decoding for text field values (`title`,
+//! `publisher`, `note`, ...), mirroring texlab's field/text handling: accent
+//! commands are decoded, case-protection `{...}` groups are stripped, `~`
+//! ties and `\,`/`\ ` spacing commands collapse to ordinary spaces, and
+//! `--`/`---` become en/em dashes. The raw field value is left untouched, so
+//! serialization back to `.bib` stays lossless.
+
+use std::borrow::Cow;
+
+use crate::BibItem;
+
+fn symbol_accent_table(cmd: char, lower_base: char) -> Option<char> {
+    match (cmd, lower_base) {
+        ('"', 'a') => Some('ä'),
+        ('"', 'e') => Some('ë'),
+        ('"', 'i') => Some('ï'),
+        ('"', 'o') => Some('ö'),
+        ('"', 'u') => Some('ü'),
+        ('"', 'y') => Some('ÿ'),
+        ('\'', 'a') => Some('á'),
+        ('\'', 'e') => Some('é'),
+        ('\'', 'i') => Some('í'),
+        ('\'', 'o') => Some('ó'),
+        ('\'', 'u') => Some('ú'),
+        ('\'', 'y') => Some('ý'),
+        ('\'', 'c') => Some('ć'),
+        ('\'', 'n') => Some('ń'),
+        ('\'', 's') => Some('ś'),
+        ('\'', 'z') => Some('ź'),
+        ('`', 'a') => Some('à'),
+        ('`', 'e') => Some('è'),
+        ('`', 'i') => Some('ì'),
+        ('`', 'o') => Some('ò'),
+        ('`', 'u') => Some('ù'),
+        ('~', 'a') => Some('ã'),
+        ('~', 'n') => Some('ñ'),
+        ('~', 'o') => Some('õ'),
+        ('^', 'a') => Some('â'),
+        ('^', 'e') => Some('ê'),
+        ('^', 'i') => Some('î'),
+        ('^', 'o') => Some('ô'),
+        ('^', 'u') => Some('û'),
+        ('=', 'a') => Some('ā'),
+        ('=', 'e') => Some('ē'),
+        ('=', 'i') => Some('ī'),
+        ('=', 'o') => Some('ō'),
+        ('=', 'u') => Some('ū'),
+        ('.', 'z') => Some('ż'),
+        ('c', 'c') => Some('ç'),
+        ('c', 's') => Some('ş'),
+        ('c', 't') => Some('ţ'),
+        ('v', 'c') => Some('č'),
+        ('v', 's') => Some('š'),
+        ('v', 'z') => Some('ž'),
+        ('v', 'r') => Some('ř'),
+        ('v', 'e') => Some('ě'),
+        ('v', 'd') => Some('ď'),
+        ('v', 'n') => Some('ň'),
+        ('v', 't') => Some('ť'),
+        ('u', 'a') => Some('ă'),
+        ('u', 'g') => Some('ğ'),
+        ('H', 'o') => Some('ő'),
+        ('H', 'u') => Some('ű'),
+        ('k', 'a') => Some('ą'),
+        ('k', 'e') => Some('ę'),
+        _ => None,
+    }
+}
+
+/// Decode a single accent command (`cmd`, e.g. `"`/`'`/`c`/`v`) applied to
+/// `base`, preserving the case of `base`.
+fn symbol_accent(cmd: char, base: char) -> Option<char> {
+    let decoded = symbol_accent_table(cmd, base.to_ascii_lowercase())?;
+    if base.is_uppercase() {
+        decoded.to_uppercase().next()
+    } else {
+        Some(decoded)
+    }
+}
+
+/// Commands that expand to a fixed string and take no argument.
+fn literal_command(name: &str) -> Option<&'static str> {
+    match name {
+        "ss" => Some("ß"),
+        "o" => Some("ø"),
+        "O" => Some("Ø"),
+        "aa" => Some("å"),
+        "AA" => Some("Å"),
+        "ae" => Some("æ"),
+        "AE" => Some("Æ"),
+        "oe" => Some("œ"),
+        "OE" => Some("Œ"),
+        "l" => Some("ł"),
+        "L" => Some("Ł"),
+        "i" => Some("ı"),
+        "j" => Some("ȷ"),
+        _ => None,
+    }
+}
+
+/// Named (letter) accent commands that take a following argument, e.g.
+/// `\c{c}` or `\v{c}`.
+fn is_named_accent(name: &str) -> bool {
+    matches!(name, "c" | "v" | "u" | "H" | "k")
+}
+
+/// Consume the argument following an accent command: either a `{...}`
+/// group (the first letter found inside is taken as the base) or a single
+/// bare character. Advances `*i` past the argument.
+fn read_accent_arg(chars: &[char], i: &mut usize) -> Option<char> {
+    while *i < chars.len() && chars[*i].is_whitespace() {
+        *i += 1;
+    }
+    if *i >= chars.len() {
+        return None;
+    }
+    if chars[*i] == '{' {
+        *i += 1;
+        let mut base = None;
+        while *i < chars.len() && chars[*i] != '}' {
+            if base.is_none() && chars[*i].is_alphabetic() {
+                base = Some(chars[*i]);
+            }
+            *i += 1;
+        }
+        if *i < chars.len() {
+            *i += 1; // closing brace
+        }
+        base
+    } else {
+        let base = chars[*i];
+        *i += 1;
+        Some(base)
+    }
+}
+
+fn decode_impl(input: &str) -> String {
+    let chars: Vec<char> = input.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => {
+                i += 1;
+                if i >= chars.len() {
+                    out.push('\\');
+                    break;
+                }
+                let next = chars[i];
+                if next == ',' || next == ' ' {
+                    out.push(' ');
+                    i += 1;
+                } else if !next.is_alphabetic() {
+                    // A symbol accent command, e.g. `\"`, `\'`, `\~`, `\^`.
+                    i += 1;
+                    if let Some(base) = read_accent_arg(&chars, &mut i) {
+                        out.push(symbol_accent(next, base).unwrap_or(base));
+                    }
+                } else {
+                    let start = i;
+                    while i < chars.len() && chars[i].is_alphabetic() {
+                        i += 1;
+                    }
+                    let name: String = chars[start..i].iter().collect();
+                    if let Some(literal) = literal_command(&name) {
+                        out.push_str(literal);
+                        // TeX treats the single space after a command name as
+                        // the command's terminator, not as text, so it's
+                        // consumed rather than rendered.
+                        if i < chars.len() && chars[i] == ' ' {
+                            i += 1;
+                        }
+                    } else if is_named_accent(&name) {
+                        if let Some(base) = read_accent_arg(&chars, &mut i) {
+                            let cmd = name.chars().next().unwrap();
+                            out.push(symbol_accent(cmd, base).unwrap_or(base));
+                        }
+                    } else {
+                        // Unknown command: leave it verbatim.
+                        out.push('\\');
+                        out.push_str(&name);
+                    }
+                }
+            }
+            '~' => {
+                out.push(' ');
+                i += 1;
+            }
+            '{' | '}' => {
+                // Redundant case-protection grouping.
+                i += 1;
+            }
+            c => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+fn needs_decoding(s: &str) -> bool {
+    s.contains('\\') || s.contains('~') || s.contains('{') || s.contains('}') || s.contains('-')
+}
+
+/// Decode TeX markup in `input` into plain Unicode. Returns a borrowed
+/// `Cow` unchanged when `input` contains no `\`, `~`, `{`, `}` or `-`;
+/// otherwise returns an owned copy, even if decoding left it unchanged.
+pub fn decode_latex(input: &str) -> Cow<'_, str> {
+    if !needs_decoding(input) {
+        return Cow::Borrowed(input);
+    }
+    let decoded = decode_impl(input);
+    Cow::Owned(decoded.replace("---", "—").replace("--", "–"))
+}
+
+impl BibItem {
+    /// The LaTeX-decoded, display-ready value of `field`. The raw, possibly
+    /// TeX-laden value stored on the entry is untouched by this call.
+    pub fn display_value(&self, field: &str) -> Option<String> {
+        self.tag(field).map(|raw| decode_latex(&raw).into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::bibfile::{TagMap, ValueFragment};
+
+    #[test]
+    fn test_decode_umlaut() {
+        assert_eq!(decode_latex("\\\"{o}"), "ö");
+        assert_eq!(decode_latex("\\\"o"), "ö");
+    }
+
+    #[test]
+    fn test_decode_acute() {
+        assert_eq!(decode_latex("caf\\'e"), "café");
+    }
+
+    #[test]
+    fn test_decode_tilde_command() {
+        assert_eq!(decode_latex("\\~{n}"), "ñ");
+    }
+
+    #[test]
+    fn test_decode_cedilla() {
+        assert_eq!(decode_latex("\\c{c}"), "ç");
+    }
+
+    #[test]
+    fn test_decode_ss() {
+        assert_eq!(decode_latex("stra\\ss e"), "straße");
+    }
+
+    #[test]
+    fn test_strips_case_protection_braces() {
+        assert_eq!(decode_latex("{NASA} rocks"), "NASA rocks");
+    }
+
+    #[test]
+    fn test_collapses_tie_and_spacing_commands() {
+        assert_eq!(decode_latex("Figure~1"), "Figure 1");
+        assert_eq!(decode_latex("a\\,b"), "a b");
+    }
+
+    #[test]
+    fn test_dash_replacement() {
+        assert_eq!(decode_latex("pages 12--34"), "pages 12–34");
+        assert_eq!(decode_latex("em---dash"), "em—dash");
+    }
+
+    #[test]
+    fn test_unchanged_input_is_borrowed() {
+        assert!(matches!(decode_latex("plain text"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_display_value_keeps_raw_lossless() {
+        let mut tags = TagMap::new();
+        tags.insert(
+            String::from("title"),
+            vec![ValueFragment::Literal(String::from("stra\\ss e"))],
+        );
+        let entry = BibItem::Entry {
+            entry_type: String::from("ARTICLE"),
+            label: String::from("label"),
+            tags,
+        };
+
+        assert_eq!(entry.display_value("title").as_deref(), Some("straße"));
+        assert_eq!(entry.tag("title").as_deref(), Some("stra\\ss e"));
+    }
+}
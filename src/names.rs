@@ -0,0 +1,365 @@
+//! Structured parsing of BibTeX name-list fields (`author`, `editor`,
+//! `translator`) into `von`/`last`/`first`/`jr` parts, following the same
+//! layout rules as BibTeX itself (see texlab's `bibtex-utils` for a
+//! reference implementation).
+
+use serde::{Deserialize, Serialize};
+
+use crate::BibItem;
+
+/// A single parsed name, split into the four parts BibTeX recognises.
+/// Any part not present in the source is an empty string. Brace groups
+/// (`{...}`) are preserved verbatim in whichever part they fall into.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Name {
+    pub first: String,
+    pub von: String,
+    pub last: String,
+    pub jr: String,
+}
+
+/// Split `field` on top-level ` and ` separators (case-insensitive, never
+/// inside a brace group) into the individual raw name strings.
+fn split_names(field: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut current: Vec<String> = Vec::new();
+    for token in top_level_tokens(field) {
+        if token.eq_ignore_ascii_case("and") {
+            if !current.is_empty() {
+                names.push(current.join(" "));
+                current.clear();
+            }
+        } else {
+            current.push(token);
+        }
+    }
+    if !current.is_empty() {
+        names.push(current.join(" "));
+    }
+    names
+}
+
+/// Split `s` into whitespace-delimited tokens, treating a `{...}` group
+/// (including any whitespace it contains) as a single opaque token.
+fn top_level_tokens(s: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0i32;
+    for c in s.chars() {
+        match c {
+            '{' => {
+                depth += 1;
+                current.push(c);
+            }
+            '}' => {
+                depth -= 1;
+                current.push(c);
+            }
+            c if c.is_whitespace() && depth == 0 => {
+                if !current.is_empty() {
+                    tokens.push(current.clone());
+                    current.clear();
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+/// Split `s` on top-level commas (never inside a brace group).
+fn split_commas(s: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0i32;
+    for c in s.chars() {
+        match c {
+            '{' => {
+                depth += 1;
+                current.push(c);
+            }
+            '}' => {
+                depth -= 1;
+                current.push(c);
+            }
+            ',' if depth == 0 => {
+                parts.push(current.trim().to_string());
+                current.clear();
+            }
+            c => current.push(c),
+        }
+    }
+    parts.push(current.trim().to_string());
+    parts
+}
+
+/// A brace-protected token (`{...}`) counts as starting with an uppercase
+/// letter, regardless of its contents.
+fn starts_lowercase(token: &str) -> bool {
+    if token.starts_with('{') {
+        return false;
+    }
+    token.chars().next().is_some_and(|c| c.is_lowercase())
+}
+
+/// Split a "von Last" token sequence into its `von` and `last` parts: `von`
+/// is the maximal leading run of lowercase-initial tokens.
+fn split_von_last(tokens: &[String]) -> (String, String) {
+    let mut von_end = 0;
+    while von_end < tokens.len() && starts_lowercase(&tokens[von_end]) {
+        von_end += 1;
+    }
+    // The von part may not consume every token: "Last" always needs at
+    // least one token left over.
+    if von_end == tokens.len() && von_end > 0 {
+        von_end -= 1;
+    }
+    (
+        tokens[..von_end].join(" "),
+        tokens[von_end..].join(" "),
+    )
+}
+
+/// Parse the "First von Last" layout (no commas).
+fn parse_first_von_last(tokens: &[String]) -> Name {
+    if tokens.is_empty() {
+        return Name::default();
+    }
+    if tokens.len() == 1 {
+        return Name {
+            last: tokens[0].clone(),
+            ..Name::default()
+        };
+    }
+
+    let mid = &tokens[1..tokens.len() - 1];
+    let mut von_start = None;
+    let mut von_end = 0;
+    for (i, token) in mid.iter().enumerate() {
+        if starts_lowercase(token) {
+            if von_start.is_none() {
+                von_start = Some(i);
+            }
+            von_end = i + 1;
+        } else if von_start.is_some() {
+            break;
+        }
+    }
+
+    match von_start {
+        Some(start) => {
+            // Indices into `tokens`: mid[i] is tokens[i + 1].
+            let first = tokens[..start + 1].join(" ");
+            let von = tokens[start + 1..von_end + 1].join(" ");
+            let last = tokens[von_end + 1..].join(" ");
+            Name {
+                first,
+                von,
+                last,
+                jr: String::new(),
+            }
+        }
+        None => Name {
+            first: tokens[..tokens.len() - 1].join(" "),
+            von: String::new(),
+            last: tokens[tokens.len() - 1].clone(),
+            jr: String::new(),
+        },
+    }
+}
+
+/// Parse a single raw name (one element of an ` and `-separated list) into
+/// its structured parts, per BibTeX's "First von Last", "von Last, First"
+/// and "von Last, Jr, First" layouts.
+fn parse_name(raw: &str) -> Name {
+    let parts = split_commas(raw);
+    match parts.as_slice() {
+        [single] => parse_first_von_last(&top_level_tokens(single)),
+        [von_last, first] => {
+            let (von, last) = split_von_last(&top_level_tokens(von_last));
+            Name {
+                first: first.clone(),
+                von,
+                last,
+                jr: String::new(),
+            }
+        }
+        [von_last, jr, first, extra @ ..] => {
+            let (von, last) = split_von_last(&top_level_tokens(von_last));
+            // BibTeX only defines three comma-separated parts for this
+            // layout; a name with more commas than that is malformed, but
+            // we fold the leftover segments back into `first` (rather than
+            // silently dropping them) so no input text is lost.
+            let mut first = first.clone();
+            if !extra.is_empty() {
+                first.push_str(", ");
+                first.push_str(&extra.join(", "));
+            }
+            Name {
+                first,
+                von,
+                last,
+                jr: jr.clone(),
+            }
+        }
+        [] => Name::default(),
+    }
+}
+
+impl BibItem {
+    /// Parse a name-list field (`author`, `editor`, `translator`, ...) into
+    /// its structured names. Returns an empty `Vec` if the tag is absent.
+    pub fn names(&self, field: &str) -> Vec<Name> {
+        match self.tag(field) {
+            Some(raw) => split_names(&raw).iter().map(|n| parse_name(n)).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn authors(&self) -> Vec<Name> {
+        self.names("author")
+    }
+
+    pub fn editors(&self) -> Vec<Name> {
+        self.names("editor")
+    }
+
+    pub fn translators(&self) -> Vec<Name> {
+        self.names("translator")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::bibfile::{TagMap, ValueFragment};
+
+    fn entry_with(field: &str, value: &str) -> BibItem {
+        let mut tags = TagMap::new();
+        tags.insert(
+            String::from(field),
+            vec![ValueFragment::Literal(String::from(value))],
+        );
+        BibItem::Entry {
+            entry_type: String::from("ARTICLE"),
+            label: String::from("label"),
+            tags,
+        }
+    }
+
+    #[test]
+    fn test_first_last() {
+        let entry = entry_with("author", "John Smith");
+        assert_eq!(
+            entry.authors(),
+            vec![Name {
+                first: String::from("John"),
+                von: String::new(),
+                last: String::from("Smith"),
+                jr: String::new(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_first_von_last() {
+        let entry = entry_with("author", "John de la Cruz");
+        assert_eq!(
+            entry.authors(),
+            vec![Name {
+                first: String::from("John"),
+                von: String::from("de la"),
+                last: String::from("Cruz"),
+                jr: String::new(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_von_last_comma_first() {
+        let entry = entry_with("author", "de la Cruz, John");
+        assert_eq!(
+            entry.authors(),
+            vec![Name {
+                first: String::from("John"),
+                von: String::from("de la"),
+                last: String::from("Cruz"),
+                jr: String::new(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_von_last_comma_jr_comma_first() {
+        let entry = entry_with("author", "de la Cruz, Jr, John");
+        assert_eq!(
+            entry.authors(),
+            vec![Name {
+                first: String::from("John"),
+                von: String::from("de la"),
+                last: String::from("Cruz"),
+                jr: String::from("Jr"),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_multiple_authors() {
+        let entry = entry_with("author", "John Smith and Jane Doe");
+        assert_eq!(
+            entry.authors(),
+            vec![
+                Name {
+                    first: String::from("John"),
+                    von: String::new(),
+                    last: String::from("Smith"),
+                    jr: String::new(),
+                },
+                Name {
+                    first: String::from("Jane"),
+                    von: String::new(),
+                    last: String::from("Doe"),
+                    jr: String::new(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_brace_protected_token_counts_as_uppercase() {
+        let entry = entry_with("author", "{van der Berg}, John");
+        assert_eq!(
+            entry.authors(),
+            vec![Name {
+                first: String::from("John"),
+                von: String::new(),
+                last: String::from("{van der Berg}"),
+                jr: String::new(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_von_last_comma_jr_comma_first_comma_extra_is_not_dropped() {
+        let entry = entry_with("author", "de la Cruz, Jr, John, Extra");
+        assert_eq!(
+            entry.authors(),
+            vec![Name {
+                first: String::from("John, Extra"),
+                von: String::from("de la"),
+                last: String::from("Cruz"),
+                jr: String::from("Jr"),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_missing_field_returns_empty() {
+        let entry = entry_with("author", "John Smith");
+        assert_eq!(entry.names("editor"), Vec::new());
+    }
+}
@@ -1,37 +1,43 @@
-use std::collections::HashMap;
-
+use nom::types::CompleteStr;
 use nom::{
-    alpha, alphanumeric, alt, complete, delimited, is_not, many1, map, named, opt, recognize,
-    separated_list, separated_pair, tag, ws,
+    alpha, alphanumeric, alt, call, complete, delimited, is_not, many1, map, named, opt,
+    recognize, separated_list, separated_nonempty_list, tag, ws, Context, Err as NomErr, Needed,
 };
 
+use crate::bibfile::{format_value, BibError, TagMap, Value, ValueFragment};
 use crate::BibItem;
 
+// All grammar rules below operate on `CompleteStr` rather than `&str`, so
+// that running out of input is always an `Error` rather than nom's
+// streaming-parser `Incomplete` (which `ws!`'s trailing whitespace match
+// cannot otherwise distinguish from "there might be more whitespace after
+// EOF"). Without this, every real (non-streamed) file whose last entry has
+// no trailing byte after its closing `}` fails to parse.
 named!(
-    quoted_string<&str, &str>,
+    quoted_string<CompleteStr, CompleteStr>,
     complete!(delimited!(
-        tag!("\""), 
+        tag!("\""),
         string,
         tag!("\"")
     ))
 );
 
 named!(
-    braced_string<&str, &str>,
+    braced_string<CompleteStr, CompleteStr>,
     complete!(delimited!(
-        tag!("{"), 
+        tag!("{"),
         string,
         tag!("}")
     ))
 );
 
 named!(
-    any_string<&str, &str>,
+    any_string<CompleteStr, CompleteStr>,
     is_not!("\"{}")
 );
 
 named!(
-    string<&str, &str>,
+    string<CompleteStr, CompleteStr>,
     recognize!(many1!(
         alt!(
             recognize!(delimited_string) |
@@ -41,40 +47,80 @@ named!(
 );
 
 named!(
-    delimited_string<&str, &str>,
+    delimited_string<CompleteStr, CompleteStr>,
     alt!( braced_string | quoted_string )
 );
 
+// `@COMMENT` bodies are arbitrary free text, not a tag value: only brace
+// nesting is significant, and an odd number of literal `"` characters
+// (plausible in ordinary prose) must not abort the parse the way it would
+// for `string`. Scan for the matching unbalanced `}` by hand instead of
+// reusing the quote-balancing `string` combinator.
+fn balanced_braces(input: CompleteStr) -> nom::IResult<CompleteStr, CompleteStr> {
+    let s = input.0;
+    let mut depth = 0usize;
+    for (i, c) in s.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' if depth == 0 => return Ok((CompleteStr(&s[i..]), CompleteStr(&s[..i]))),
+            '}' => depth -= 1,
+            _ => {}
+        }
+    }
+    Err(NomErr::Incomplete(Needed::Unknown))
+}
+
+// A bare (unquoted, unbraced) token is either a number, which is taken
+// literally, or an identifier, which refers to an `@STRING` macro and is
+// resolved later by `BibItem::resolve_strings`.
+named!(
+    value_fragment<CompleteStr, ValueFragment>,
+    alt!(
+        map!(delimited_string, |s: CompleteStr| ValueFragment::Literal(String::from(s.0))) |
+        map!(alphanumeric, |s: CompleteStr| {
+            if s.0.chars().all(|c| c.is_ascii_digit()) {
+                ValueFragment::Literal(String::from(s.0))
+            } else {
+                ValueFragment::Ident(String::from(s.0))
+            }
+        })
+    )
+);
+
+// A tag value is a `#`-separated list of fragments, e.g.
+// `author = pre # " and " # post`.
 named!(
-    tag_pair<&str, (&str, &str)>,
+    value<CompleteStr, Value>,
+    ws!(separated_nonempty_list!(ws!(tag!("#")), value_fragment))
+);
+
+named!(
+    tag_pair<CompleteStr, (CompleteStr, Value)>,
     ws!(
         separated_pair!(
             alpha,
             tag!("="),
-            alt!(
-                alphanumeric |
-                delimited_string
-            )
+            value
         )
     )
 );
 
 named!(
-    tag_list<&str, HashMap<String, String>>,
+    tag_list<CompleteStr, TagMap>,
     map!(
         separated_list!(tag!(","), complete!(tag_pair)),
         |tpl_vec| {
-            let mut hm = HashMap::new();
+            let mut tags = TagMap::new();
             for (k, v) in tpl_vec.into_iter() {
-                hm.insert(k.to_lowercase(), String::from(v));
+                tags.insert(k.0.to_lowercase(), v);
             }
-            hm
+            tags
         }
     )
 );
 
 named!(
-    bib_entry<&str, BibItem>,
+    bib_entry<CompleteStr, BibItem>,
     ws!(alt!(
         // STRING
         do_parse!(
@@ -83,16 +129,19 @@ named!(
             (BibItem::String(tags))
         ) |
 
-        // PREAMBLE
+        // PREAMBLE: content is a tag-value-like, possibly `#`-concatenated,
+        // string of TeX setup code.
         do_parse!(
             tag!("@PREAMBLE") >>
-            (BibItem::Preamble)
+            content: delimited!(tag!("{"), value, tag!("}")) >>
+            (BibItem::Preamble(format_value(&content)))
         ) |
 
-        // COMMENT
+        // COMMENT: content is arbitrary, nested-brace-balanced text.
         do_parse!(
             tag!("@COMMENT") >>
-            (BibItem::Comment)
+            content: delimited!(tag!("{"), call!(balanced_braces), tag!("}")) >>
+            (BibItem::Comment(String::from(content.0)))
         ) |
 
         // Entry type
@@ -106,28 +155,102 @@ named!(
             opt!(tag!(",")) >>
             tag!("}") >>
             (BibItem::Entry {
-                entry_type: typ.to_uppercase(),
-                label: String::from(label),
+                entry_type: typ.0.to_uppercase(),
+                label: String::from(label.0),
                 tags
             })
         )
     ))
 );
 
-named!(
-    pub bibfile<&str, Vec<BibItem>>,
-    ws!(many0!(complete!(bib_entry)))
-);
+/// The 1-based line and column of byte `offset` within `input`.
+fn line_col(input: &str, offset: usize) -> (usize, usize) {
+    let mut line = 1;
+    let mut column = 1;
+    for c in input[..offset].chars() {
+        if c == '\n' {
+            line += 1;
+            column = 1;
+        } else {
+            column += 1;
+        }
+    }
+    (line, column)
+}
+
+/// A short description of a nom failure, derived from its `ErrorKind`.
+fn describe_nom_error(err: &NomErr<CompleteStr>) -> String {
+    match err {
+        NomErr::Incomplete(_) => "unexpected end of input".to_string(),
+        NomErr::Error(Context::Code(_, kind)) | NomErr::Failure(Context::Code(_, kind)) => {
+            format!("expected {:?}", kind)
+        }
+        #[allow(unreachable_patterns)]
+        _ => "parse error".to_string(),
+    }
+}
+
+/// Best-effort guess at the label of the entry starting at `remaining`, for
+/// use in a diagnostic message. Returns `None` if `remaining` doesn't even
+/// look like the start of an entry header.
+fn guess_label(remaining: &str) -> Option<String> {
+    let trimmed = remaining.trim_start();
+    let after_at = trimmed.strip_prefix('@')?;
+    let after_type = after_at.trim_start_matches(|c: char| c.is_alphabetic());
+    let rest = after_type.trim_start().strip_prefix('{')?;
+    let end = rest.find([',', '}'])?;
+    let label = rest[..end].trim();
+    if label.is_empty() {
+        None
+    } else {
+        Some(label.to_string())
+    }
+}
+
+/// Parse `input` entry-by-entry, producing a `BibError` that points at the
+/// first unparsable entry while preserving every entry parsed before it.
+pub fn parse_diagnostic(input: &str) -> Result<Vec<BibItem>, BibError> {
+    let mut items = Vec::new();
+    let mut remaining = input;
+    loop {
+        let trimmed = remaining.trim_start();
+        if trimmed.is_empty() {
+            return Ok(items);
+        }
+        match bib_entry(CompleteStr(trimmed)) {
+            Ok((rest, item)) => {
+                items.push(item);
+                remaining = rest.0;
+            }
+            Err(err) => {
+                let offset = input.len() - trimmed.len();
+                let (line, column) = line_col(input, offset);
+                return Err(BibError::Parse {
+                    offset,
+                    line,
+                    column,
+                    label: guess_label(trimmed),
+                    message: describe_nom_error(&err),
+                    partial: items,
+                });
+            }
+        }
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn lit(s: &str) -> Value {
+        vec![ValueFragment::Literal(String::from(s))]
+    }
+
     fn get_expected() -> BibItem {
-        let mut expected_props = HashMap::new();
-        expected_props.insert(String::from("author"), String::from("Some Body"));
-        expected_props.insert(String::from("title"), String::from("Some Thing"));
-        expected_props.insert(String::from("date"), String::from("2000"));
+        let mut expected_props = TagMap::new();
+        expected_props.insert(String::from("author"), lit("Some Body"));
+        expected_props.insert(String::from("title"), lit("Some Thing"));
+        expected_props.insert(String::from("date"), lit("2000"));
         BibItem::Entry {
             entry_type: String::from("ARTICLE"),
             label: String::from("label"),
@@ -137,24 +260,24 @@ mod tests {
 
     #[test]
     fn test_quoted_string() {
-        let line = "\"This is a string\"\0";
-        let (i, o) = quoted_string(line).unwrap();
+        let line = "\"This is a string\"";
+        let (_, o) = quoted_string(CompleteStr(line)).unwrap();
 
-        assert_eq!(o, "This is a string");
+        assert_eq!(o.0, "This is a string");
     }
 
     #[test]
     fn test_braced_string() {
-        let line = "{test string}\0";
-        let (_, o) = braced_string(line).unwrap();
+        let line = "{test string}";
+        let (_, o) = braced_string(CompleteStr(line)).unwrap();
 
-        assert_eq!(o, "test string");
+        assert_eq!(o.0, "test string");
     }
 
     #[test]
     fn test_kv_pair() {
-        let line = "key=\"value\"\0";
-        let r = tag_pair(line);
+        let line = "key=\"value\"";
+        let r = tag_pair(CompleteStr(line));
 
         if let Err(ref inc) = r {
             println!("{:?}", inc);
@@ -162,21 +285,42 @@ mod tests {
             let (_, (k, v)) = r.unwrap();
 
             println!("{:?} = {:?}", k, v);
-            assert_eq!(k, "key");
-            assert_eq!(v, "value");
+            assert_eq!(k.0, "key");
+            assert_eq!(v, lit("value"));
         }
     }
 
     #[test]
     fn test_kv_list() {
-        let line = "keyone=\"value1\",\nkeytwo={value2}\0";
-        let mut expected = HashMap::new();
-        expected.insert(String::from("keyone"), String::from("value1"));
-        expected.insert(String::from("keytwo"), String::from("value2"));
-        let (_, o) = tag_list(line).unwrap();
+        let line = "keyone=\"value1\",\nkeytwo={value2}";
+        let mut expected = TagMap::new();
+        expected.insert(String::from("keyone"), lit("value1"));
+        expected.insert(String::from("keytwo"), lit("value2"));
+        let (_, o) = tag_list(CompleteStr(line)).unwrap();
         assert_eq!(o, expected);
     }
 
+    #[test]
+    fn test_value_concatenation() {
+        let line = "pre # \" and \" # post";
+        let (_, o) = value(CompleteStr(line)).unwrap();
+        assert_eq!(
+            o,
+            vec![
+                ValueFragment::Ident(String::from("pre")),
+                ValueFragment::Literal(String::from(" and ")),
+                ValueFragment::Ident(String::from("post")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_value_bare_number_is_literal() {
+        let line = "2000";
+        let (_, o) = value(CompleteStr(line)).unwrap();
+        assert_eq!(o, lit("2000"));
+    }
+
     #[test]
     fn test_full_bib_item() {
         let line = "\
@@ -184,9 +328,9 @@ mod tests {
     author = \"Some Body\",
     title = \"Some Thing\",
     date = \"2000\"
-}\0";
+}";
         let expected = get_expected();
-        let (_, o) = bib_entry(line).unwrap();
+        let (_, o) = bib_entry(CompleteStr(line)).unwrap();
         assert_eq!(o, expected);
     }
 
@@ -197,9 +341,9 @@ mod tests {
     author = \"Some Body\",
     title = \"Some Thing\",
     date = 2000
-}\0";
+}";
         let expected = get_expected();
-        let (_, o) = bib_entry(line).unwrap();
+        let (_, o) = bib_entry(CompleteStr(line)).unwrap();
         assert_eq!(o, expected);
     }
 
@@ -210,9 +354,9 @@ mod tests {
     author = \"Some Body\",
     title = \"Some Thing\",
     date = \"2000\"
-}\0";
+}";
         let expected = get_expected();
-        let (_, o) = bib_entry(line).unwrap();
+        let (_, o) = bib_entry(CompleteStr(line)).unwrap();
         assert_eq!(o, expected);
     }
 
@@ -223,32 +367,115 @@ mod tests {
     author = \"Some Body\",
     title = \"Some Thing\",
     date = \"2000\",
-}\0";
+}";
         let expected = get_expected();
-        let (_, o) = bib_entry(line).unwrap();
+        let (_, o) = bib_entry(CompleteStr(line)).unwrap();
         assert_eq!(o, expected);
     }
 
     fn get_expected_strings() -> BibItem {
-        let mut hm = HashMap::new();
-        hm.insert(String::from("key"), String::from("value"));
-        hm.insert(String::from("another"), String::from("value"));
+        let mut tags = TagMap::new();
+        tags.insert(String::from("key"), lit("value"));
+        tags.insert(String::from("another"), lit("value"));
 
-        BibItem::String(hm)
+        BibItem::String(tags)
     }
 
     #[test]
     fn test_replacement_strings() {
-        let line = "@STRING { key = \"value\", another = \"value\" }\0";
+        let line = "@STRING { key = \"value\", another = \"value\" }";
         let expected = get_expected_strings();
-        let (_, o) = bib_entry(line).unwrap();
+        let (_, o) = bib_entry(CompleteStr(line)).unwrap();
 
         assert_eq!(o, expected);
     }
 
     #[test]
-    fn test_bib_file() {
-        let line = "\
+    fn test_preamble_captures_content() {
+        let line = "@PREAMBLE{\"\\makeatletter\"}";
+        let (_, o) = bib_entry(CompleteStr(line)).unwrap();
+        assert_eq!(o, BibItem::Preamble(String::from("\\makeatletter")));
+    }
+
+    #[test]
+    fn test_preamble_concatenation() {
+        let line = "@PREAMBLE{\"\\newcommand\" # \"{\\foo}\"}";
+        let (_, o) = bib_entry(CompleteStr(line)).unwrap();
+        assert_eq!(
+            o,
+            BibItem::Preamble(String::from("\\newcommand{\\foo}"))
+        );
+    }
+
+    #[test]
+    fn test_comment_captures_content() {
+        let line = "@COMMENT{this is a comment}";
+        let (_, o) = bib_entry(CompleteStr(line)).unwrap();
+        assert_eq!(o, BibItem::Comment(String::from("this is a comment")));
+    }
+
+    #[test]
+    fn test_comment_tolerates_an_unmatched_quote() {
+        let line = "@COMMENT{5\" screen}";
+        let (_, o) = bib_entry(CompleteStr(line)).unwrap();
+        assert_eq!(o, BibItem::Comment(String::from("5\" screen")));
+    }
+
+    #[test]
+    fn test_comment_respects_nested_braces() {
+        let line = "@COMMENT{outer {inner} text}";
+        let (_, o) = bib_entry(CompleteStr(line)).unwrap();
+        assert_eq!(o, BibItem::Comment(String::from("outer {inner} text")));
+    }
+
+    #[test]
+    fn test_parse_diagnostic_reports_offending_entry() {
+        let input = "\
+@article{good,
+    title = \"A Title\",
+}
+
+this is not a valid entry
+";
+        let err = parse_diagnostic(input).unwrap_err();
+        match err {
+            BibError::Parse {
+                partial,
+                line,
+                label,
+                ..
+            } => {
+                assert_eq!(partial.len(), 1);
+                assert_eq!(line, 5);
+                assert_eq!(label, None);
+            }
+            BibError::Io(_) => panic!("expected a parse error"),
+        }
+    }
+
+    #[test]
+    fn test_parse_diagnostic_guesses_label() {
+        let input = "@article{broken,\n    title = ,\n}\n";
+        let err = parse_diagnostic(input).unwrap_err();
+        match err {
+            BibError::Parse { label, .. } => assert_eq!(label.as_deref(), Some("broken")),
+            BibError::Io(_) => panic!("expected a parse error"),
+        }
+    }
+
+    /// A single, perfectly valid entry with no trailing byte after its
+    /// closing `}` used to hit nom's `Incomplete` at true EOF (see the
+    /// module-level note above `quoted_string`) rather than succeeding.
+    #[test]
+    fn test_parse_diagnostic_succeeds_on_unterminated_valid_entry() {
+        let input = "@article{good,\n    title = \"A Title\",\n}";
+        let items = parse_diagnostic(input).unwrap();
+        assert_eq!(items.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_diagnostic_parses_a_multi_entry_file() {
+        let input = "\
 @article {label,
     title = \"article\",
     author = {somebody},
@@ -259,11 +486,29 @@ mod tests {
     title = \"book\",
     author = {somebody else},
     date = 2000,
-}\0";
+}";
 
-        let (_, r) = bibfile(line).unwrap();
-        println!("{:?}", r);
-        assert_eq!(r.len(), 2);
+        let items = parse_diagnostic(input).unwrap();
+        assert_eq!(items.len(), 2);
     }
 
+    #[test]
+    fn test_load_parses_a_real_file_with_no_trailing_sentinel() {
+        let dir = std::env::temp_dir();
+        let path = dir.join(format!(
+            "bibtexrs_test_{}.bib",
+            std::process::id()
+        ));
+        std::fs::write(
+            &path,
+            "@article{good,\n    title = \"A Title\",\n    year = 2000\n}\n",
+        )
+        .unwrap();
+
+        let result = BibItem::load(&path);
+        std::fs::remove_file(&path).ok();
+
+        let file = result.unwrap();
+        assert_eq!(file.len(), 1);
+    }
 }